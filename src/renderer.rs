@@ -5,13 +5,14 @@ use {
     BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, Buffer,
     BufferBinding, BufferBindingType, BufferDescriptor, BufferUsages, Color,
     CommandEncoderDescriptor, Device, DeviceDescriptor, Extent3d, Features, FragmentState,
-    ImageCopyTexture, ImageDataLayout, Instance, Limits, LoadOp, MemoryHints, MultisampleState,
-    Operations, Origin3d, PipelineCompilationOptions, PipelineLayoutDescriptor, PowerPreference,
-    PrimitiveState, Queue, RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline,
-    RenderPipelineDescriptor, RequestAdapterOptions, Sampler, SamplerBindingType,
-    SamplerDescriptor, ShaderStages, StoreOp, Surface, SurfaceConfiguration, Texture,
-    TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType,
+    ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, Instance, Limits, LoadOp, Maintain,
+    MapMode, MemoryHints, MultisampleState, Operations, Origin3d, PipelineCompilationOptions,
+    PipelineLayoutDescriptor, PowerPreference, PrimitiveState, Queue, RenderPassColorAttachment,
+    RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, RequestAdapterOptions, Sampler,
+    SamplerBindingType, SamplerDescriptor, ShaderStages, StoreOp, Surface, SurfaceConfiguration,
+    Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType,
     TextureUsages, TextureView, TextureViewDescriptor, TextureViewDimension, VertexState,
+    COPY_BYTES_PER_ROW_ALIGNMENT,
   },
 };
 
@@ -66,7 +67,8 @@ impl Renderer {
       format: self.texture_format,
       usage: TextureUsages::RENDER_ATTACHMENT
         | TextureUsages::TEXTURE_BINDING
-        | TextureUsages::COPY_DST,
+        | TextureUsages::COPY_DST
+        | TextureUsages::COPY_SRC,
       view_formats: &[self.texture_format],
     });
 
@@ -343,6 +345,92 @@ impl Renderer {
     Ok(())
   }
 
+  /// Copy the texture of the target at `index` into a `Vec` of tightly packed
+  /// RGBA bytes, for use in tests or headless rendering. `self.texture_format`
+  /// is whatever the surface prefers, which on most desktop backends is a
+  /// BGRA variant, so the red and blue channels are swapped back into RGBA
+  /// order before returning.
+  pub fn read_target(&self, index: usize) -> Result<Vec<u8>> {
+    let target = self.targets.get(index).context("invalid target index")?;
+
+    let width = self.config.width;
+    let height = self.config.height;
+
+    let unpadded_bytes_per_row = width * 4;
+
+    let padded_bytes_per_row =
+      unpadded_bytes_per_row.div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT) * COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    let buffer = self.device.create_buffer(&BufferDescriptor {
+      label: Some("texture readback buffer"),
+      mapped_at_creation: false,
+      size: u64::from(padded_bytes_per_row) * u64::from(height),
+      usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+    });
+
+    let mut encoder = self
+      .device
+      .create_command_encoder(&CommandEncoderDescriptor::default());
+
+    encoder.copy_texture_to_buffer(
+      ImageCopyTexture {
+        texture: &target.texture,
+        mip_level: 0,
+        origin: Origin3d::ZERO,
+        aspect: TextureAspect::All,
+      },
+      ImageCopyBuffer {
+        buffer: &buffer,
+        layout: ImageDataLayout {
+          offset: 0,
+          bytes_per_row: Some(padded_bytes_per_row),
+          rows_per_image: Some(height),
+        },
+      },
+      Extent3d {
+        width,
+        height,
+        depth_or_array_layers: 1,
+      },
+    );
+
+    self.queue.submit([encoder.finish()]);
+
+    let slice = buffer.slice(..);
+
+    let (tx, rx) = std::sync::mpsc::channel();
+
+    slice.map_async(MapMode::Read, move |result| {
+      tx.send(result).ok();
+    });
+
+    self.device.poll(Maintain::Wait);
+
+    rx.recv().context("failed to map readback buffer")??;
+
+    let padded = slice.get_mapped_range();
+
+    let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+
+    for row in padded.chunks(padded_bytes_per_row as usize) {
+      pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+    }
+
+    drop(padded);
+    buffer.unmap();
+
+    if matches!(
+      self.texture_format,
+      TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb
+    ) {
+      for pixel in pixels.chunks_mut(4) {
+        pixel.swap(0, 2);
+      }
+    }
+
+    Ok(pixels)
+  }
+
   pub(crate) fn resize(&mut self, size: PhysicalSize<u32>) {
     self.config.width = size.width.max(1);
     self.config.height = size.height.max(1);